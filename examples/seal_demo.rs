@@ -0,0 +1,177 @@
+//! End-to-end walk through the sealing API.
+//!
+//! Pads an input file (or generated data) into a sector, seals it, writes the replica and the
+//! pre-commit metadata, proves, verifies and finally unseals a range of the sector again.
+//!
+//! The sector size is given in bytes and defaults to 2KiB. Sectors are held in memory, so only
+//! the small sizes up to 512MiB are supported. The Groth parameters for the chosen size must be
+//! available in the parameter cache, e.g. by running `paramcache` from filecoin-proofs
+//! beforehand.
+//!
+//! ```
+//! cargo run --release --example seal_demo -- [sector-size] [input-file] [output-dir]
+//! ```
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::Read;
+use std::path::PathBuf;
+
+use anyhow::{bail, ensure, Context, Result};
+use filecoin_proofs_api::seal::{
+    add_piece, clear_cache, get_unsealed_range, seal_commit_phase1, seal_commit_phase2,
+    seal_pre_commit_phase1, seal_pre_commit_phase2, verify_seal,
+};
+use filecoin_proofs_api::{
+    ApiVersion, PaddedBytesAmount, ProverId, RegisteredSealProof, SectorId, Ticket,
+    UnpaddedByteIndex, UnpaddedBytesAmount, REGISTERED_SEAL_PROOFS,
+};
+
+const DEFAULT_SECTOR_SIZE: u64 = 2 << 10;
+const MAX_SECTOR_SIZE: u64 = 512 << 20;
+const PROVER_ID: ProverId = [1u8; 32];
+const TICKET: Ticket = [2u8; 32];
+const SEED: Ticket = [3u8; 32];
+
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let sector_size = match args.next() {
+        Some(arg) => arg
+            .parse::<u64>()
+            .with_context(|| format!("invalid sector size {:?}", arg))?,
+        None => DEFAULT_SECTOR_SIZE,
+    };
+    let input = args.next().map(PathBuf::from);
+    let out_dir = args
+        .next()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::temp_dir().join("seal_demo"));
+
+    let registered_proof = seal_proof_for_size(sector_size)?;
+    println!("using {:?}", registered_proof);
+    let sector_id = SectorId::from(1);
+    let piece_size = UnpaddedBytesAmount::from(PaddedBytesAmount(sector_size));
+
+    // Read the input and fill it up to a full sector with zeroes.
+    let mut data = match input {
+        Some(ref path) => {
+            let mut data = Vec::new();
+            File::open(path)
+                .with_context(|| format!("could not open input {:?}", path))?
+                .read_to_end(&mut data)?;
+            data
+        }
+        None => (0..u64::from(piece_size)).map(|i| i as u8).collect(),
+    };
+    ensure!(
+        data.len() as u64 <= u64::from(piece_size),
+        "input is larger than a {} byte sector",
+        u64::from(piece_size)
+    );
+    data.resize(u64::from(piece_size) as usize, 0);
+
+    let cache_path = out_dir.join("cache");
+    let staged_path = out_dir.join("staged");
+    let sealed_path = out_dir.join("sealed");
+    let unsealed_path = out_dir.join("unsealed");
+    let metadata_path = out_dir.join("metadata");
+    fs::create_dir_all(&cache_path)?;
+
+    println!("padding {} bytes into {:?}", data.len(), staged_path);
+    let staged = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&staged_path)?;
+    let (piece_info, _) = add_piece(registered_proof, &data[..], staged, piece_size, &[])?;
+    let piece_infos = vec![piece_info];
+    File::create(&sealed_path)?;
+
+    println!("sealing into {:?}", sealed_path);
+    let phase1_output = seal_pre_commit_phase1(
+        registered_proof,
+        &cache_path,
+        &staged_path,
+        &sealed_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        &piece_infos,
+    )?;
+    let pre_commit = seal_pre_commit_phase2(phase1_output, &cache_path, &sealed_path)?;
+    fs::write(&metadata_path, bincode::serialize(&pre_commit)?)?;
+    println!("comm_r: {:?}", pre_commit.comm_r);
+    println!("comm_d: {:?}", pre_commit.comm_d);
+
+    println!("proving");
+    let comm_r = pre_commit.comm_r;
+    let comm_d = pre_commit.comm_d;
+    let commit_phase1_output = seal_commit_phase1(
+        &cache_path,
+        &sealed_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        SEED,
+        pre_commit,
+        &piece_infos,
+    )?;
+    let commit_output = seal_commit_phase2(commit_phase1_output, PROVER_ID, sector_id)?;
+
+    println!("verifying");
+    let valid = verify_seal(
+        registered_proof,
+        comm_r,
+        comm_d,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        SEED,
+        &commit_output.proof,
+    )?;
+    ensure!(valid, "seal proof failed to verify");
+
+    println!("unsealing into {:?}", unsealed_path);
+    let offset = 64;
+    let len = 512;
+    get_unsealed_range(
+        registered_proof,
+        cache_path.clone(),
+        sealed_path,
+        unsealed_path.clone(),
+        PROVER_ID,
+        sector_id,
+        comm_d,
+        TICKET,
+        UnpaddedByteIndex(offset),
+        UnpaddedBytesAmount(len),
+    )?;
+    let unsealed = fs::read(&unsealed_path)?;
+    ensure!(
+        unsealed[..] == data[offset as usize..(offset + len) as usize],
+        "unsealed range does not match the original data"
+    );
+
+    clear_cache(sector_size, &cache_path)?;
+    println!("done");
+
+    Ok(())
+}
+
+/// Picks the V1_1 seal proof for `sector_size`.
+fn seal_proof_for_size(sector_size: u64) -> Result<RegisteredSealProof> {
+    ensure!(
+        sector_size <= MAX_SECTOR_SIZE,
+        "sector size {} is too large for this demo, at most {} is supported",
+        sector_size,
+        MAX_SECTOR_SIZE
+    );
+
+    match REGISTERED_SEAL_PROOFS.iter().copied().find(|proof| {
+        u64::from(proof.sector_size()) == sector_size && proof.version() == ApiVersion::V1_1_0
+    }) {
+        Some(proof) => Ok(proof),
+        None => bail!("no registered seal proof for sector size {}", sector_size),
+    }
+}
@@ -0,0 +1,205 @@
+//! Generates the Groth parameters, verifying keys and parameter metadata for all registered
+//! proofs that are missing from the parameter cache, the same way `paramcache` from
+//! filecoin-proofs does, and writes a manifest of their digests to `paramgen.json` in the
+//! parameter cache.
+//!
+//! ```
+//! cargo run --release --features paramgen --bin paramgen -- [sector-size ...]
+//! ```
+//!
+//! Without arguments every registered sector size is generated, which takes a very long time
+//! and a lot of memory for the production sizes. Entries of an existing manifest are kept, so
+//! sector sizes can be generated in separate runs.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{ensure, Context, Result};
+use filecoin_proofs_api::preflight::parameter_file_digest;
+use filecoin_proofs_api::{
+    MerkleTreeTrait, PoStType, RegisteredPoStProof, RegisteredSealProof, REGISTERED_POST_PROOFS,
+    REGISTERED_SEAL_PROOFS,
+};
+use filecoin_proofs_v1::constants::DefaultPieceHasher;
+use filecoin_proofs_v1::parameters::{
+    public_params, window_post_public_params, winning_post_public_params,
+};
+use filecoin_proofs_v1::{with_shape, PaddedBytesAmount, PoRepProofPartitions};
+use rand::rngs::OsRng;
+use serde_json::{json, Map, Value};
+use storage_proofs_core::compound_proof::CompoundProof;
+use storage_proofs_core::parameter_cache::{parameter_cache_dir, CacheableParameters};
+use storage_proofs_porep::stacked::{StackedCompound, StackedDrg};
+use storage_proofs_post::fallback::{FallbackPoSt, FallbackPoStCircuit, FallbackPoStCompound};
+
+const MANIFEST_FILE: &str = "paramgen.json";
+
+fn main() -> Result<()> {
+    let sector_sizes = env::args()
+        .skip(1)
+        .map(|arg| {
+            arg.parse::<u64>()
+                .with_context(|| format!("invalid sector size {:?}", arg))
+        })
+        .collect::<Result<BTreeSet<u64>>>()?;
+    let selected =
+        |sector_size: u64| sector_sizes.is_empty() || sector_sizes.contains(&sector_size);
+
+    let seal_proofs: Vec<RegisteredSealProof> = REGISTERED_SEAL_PROOFS
+        .iter()
+        .copied()
+        .filter(|proof| selected(u64::from(proof.sector_size())))
+        .collect();
+    let post_proofs: Vec<RegisteredPoStProof> = REGISTERED_POST_PROOFS
+        .iter()
+        .copied()
+        .filter(|proof| selected(u64::from(proof.sector_size())))
+        .collect();
+    ensure!(
+        !seal_proofs.is_empty() || !post_proofs.is_empty(),
+        "no registered proofs for sector sizes {:?}",
+        sector_sizes
+    );
+
+    let manifest_path = parameter_cache_dir().join(MANIFEST_FILE);
+    let mut manifest = read_manifest(&manifest_path)?;
+
+    for proof in seal_proofs {
+        eprintln!("generating parameters for {:?}", proof);
+        with_shape!(u64::from(proof.sector_size()), generate_seal_params, proof)?;
+
+        let sector_size = u64::from(proof.sector_size());
+        add_manifest_entry(&mut manifest, &proof.cache_params_path()?, sector_size)?;
+        add_manifest_entry(
+            &mut manifest,
+            &proof.cache_verifying_key_path()?,
+            sector_size,
+        )?;
+    }
+    for proof in post_proofs {
+        eprintln!("generating parameters for {:?}", proof);
+        with_shape!(u64::from(proof.sector_size()), generate_post_params, proof)?;
+
+        let sector_size = u64::from(proof.sector_size());
+        add_manifest_entry(&mut manifest, &proof.cache_params_path()?, sector_size)?;
+        add_manifest_entry(
+            &mut manifest,
+            &proof.cache_verifying_key_path()?,
+            sector_size,
+        )?;
+    }
+
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&Value::Object(manifest))?,
+    )
+    .with_context(|| format!("could not write manifest {:?}", manifest_path))?;
+    eprintln!("wrote manifest {:?}", manifest_path);
+
+    Ok(())
+}
+
+fn generate_seal_params<Tree: 'static + MerkleTreeTrait>(
+    registered_proof: RegisteredSealProof,
+) -> Result<()> {
+    let config = registered_proof.as_v1_config();
+    let public_params = public_params::<Tree>(
+        PaddedBytesAmount::from(config),
+        usize::from(PoRepProofPartitions::from(config)),
+        config.porep_id,
+        config.api_version,
+    )?;
+
+    // Each lookup generates and caches the file if it is missing.
+    let circuit = <StackedCompound<Tree, DefaultPieceHasher> as CompoundProof<
+        StackedDrg<'_, Tree, DefaultPieceHasher>,
+        _,
+    >>::blank_circuit(&public_params);
+    StackedCompound::<Tree, DefaultPieceHasher>::get_param_metadata(circuit, &public_params)?;
+
+    let circuit = <StackedCompound<Tree, DefaultPieceHasher> as CompoundProof<
+        StackedDrg<'_, Tree, DefaultPieceHasher>,
+        _,
+    >>::blank_circuit(&public_params);
+    StackedCompound::<Tree, DefaultPieceHasher>::get_groth_params(
+        Some(&mut OsRng),
+        circuit,
+        &public_params,
+    )?;
+
+    let circuit = <StackedCompound<Tree, DefaultPieceHasher> as CompoundProof<
+        StackedDrg<'_, Tree, DefaultPieceHasher>,
+        _,
+    >>::blank_circuit(&public_params);
+    StackedCompound::<Tree, DefaultPieceHasher>::get_verifying_key(
+        Some(&mut OsRng),
+        circuit,
+        &public_params,
+    )?;
+
+    Ok(())
+}
+
+fn generate_post_params<Tree: 'static + MerkleTreeTrait>(
+    registered_proof: RegisteredPoStProof,
+) -> Result<()> {
+    let config = registered_proof.as_v1_config();
+    let public_params = match config.typ {
+        PoStType::Winning => winning_post_public_params::<Tree>(&config)?,
+        PoStType::Window => window_post_public_params::<Tree>(&config)?,
+    };
+
+    let circuit: FallbackPoStCircuit<Tree> = <FallbackPoStCompound<Tree> as CompoundProof<
+        FallbackPoSt<'_, Tree>,
+        FallbackPoStCircuit<Tree>,
+    >>::blank_circuit(&public_params);
+    FallbackPoStCompound::<Tree>::get_param_metadata(circuit, &public_params)?;
+
+    let circuit: FallbackPoStCircuit<Tree> = <FallbackPoStCompound<Tree> as CompoundProof<
+        FallbackPoSt<'_, Tree>,
+        FallbackPoStCircuit<Tree>,
+    >>::blank_circuit(&public_params);
+    FallbackPoStCompound::<Tree>::get_groth_params(Some(&mut OsRng), circuit, &public_params)?;
+
+    let circuit: FallbackPoStCircuit<Tree> = <FallbackPoStCompound<Tree> as CompoundProof<
+        FallbackPoSt<'_, Tree>,
+        FallbackPoStCircuit<Tree>,
+    >>::blank_circuit(&public_params);
+    FallbackPoStCompound::<Tree>::get_verifying_key(Some(&mut OsRng), circuit, &public_params)?;
+
+    Ok(())
+}
+
+fn read_manifest(path: &Path) -> Result<Map<String, Value>> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+
+    let data = fs::read(path).with_context(|| format!("could not read manifest {:?}", path))?;
+    serde_json::from_slice(&data).with_context(|| format!("invalid manifest {:?}", path))
+}
+
+/// Adds an entry in the layout of `parameters.json`, without the cid, which is only known once
+/// the file has been published.
+fn add_manifest_entry(
+    manifest: &mut Map<String, Value>,
+    path: &Path,
+    sector_size: u64,
+) -> Result<()> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("invalid parameter file name {:?}", path))?;
+
+    manifest.insert(
+        name.to_string(),
+        json!({
+            "digest": parameter_file_digest(path)?,
+            "sector_size": sector_size,
+        }),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+//! Export of verifying keys and seal public inputs for Groth16 verifiers outside of Rust.
+//!
+//! Both formats start with the same header, all integers are little endian:
+//!
+//! | bytes | field                                                          |
+//! |-------|----------------------------------------------------------------|
+//! | 4     | magic, `FPVK` for a verifying key, `FPPI` for public inputs    |
+//! | 1     | format version, currently 1                                    |
+//! | 1     | curve, 1 for BLS12-381                                         |
+//! | 1     | proof kind, 0 for seal and 1 for PoSt proofs                   |
+//! | 8     | registered proof id, the value of the registered proof enum    |
+//!
+//! A verifying key continues with the number of public inputs per partition as a u32, the
+//! length of the key as a u32 and the key itself, as written by bellperson: the uncompressed
+//! points `alpha_g1`, `beta_g1`, `beta_g2`, `gamma_g2`, `delta_g1` and `delta_g2`, the number
+//! of `ic` points as a big endian u32, and the uncompressed `ic` points.
+//!
+//! Public inputs continue with the number of partitions and the number of inputs per
+//! partition, both as u32, followed by every input of every partition as a 32 byte little
+//! endian scalar. A seal proof verifies if each partition proof verifies against its inputs.
+//!
+//! PoSt public inputs are not exported: they are derived from the challenged sectors inside
+//! filecoin-proofs while verifying, and this crate has no API which produces them.
+//!
+//! `fixtures/verifying_key_2kib_v1_1.bin` is a test vector of the verifying key format: the
+//! export of a `StackedDrg2KiBV1_1` key whose points are all generators, with three `ic` points.
+
+use std::fs;
+
+use anyhow::{ensure, Context, Result};
+use bellperson::bls::Bls12;
+use bellperson::groth16;
+
+use crate::{
+    seal, Commitment, ProverId, RegisteredPoStProof, RegisteredSealProof, SectorId, Ticket,
+};
+
+pub const FORMAT_VERSION: u8 = 1;
+pub const CURVE_BLS12_381: u8 = 1;
+
+const VERIFYING_KEY_MAGIC: &[u8; 4] = b"FPVK";
+const PUBLIC_INPUTS_MAGIC: &[u8; 4] = b"FPPI";
+
+/// Magic, version, curve, proof kind and registered proof id.
+const HEADER_LEN: usize = 15;
+
+const PROOF_KIND_SEAL: u8 = 0;
+const PROOF_KIND_POST: u8 = 1;
+
+/// Exports the verifying key of `registered_proof` from the parameter cache.
+pub fn export_seal_verifying_key(registered_proof: RegisteredSealProof) -> Result<Vec<u8>> {
+    ensure!(
+        registered_proof.major_version() == 1,
+        "unusupported version"
+    );
+
+    let path = registered_proof.cache_verifying_key_path()?;
+    let verifying_key =
+        fs::read(&path).with_context(|| format!("could not read verifying key {:?}", path))?;
+
+    export_verifying_key(PROOF_KIND_SEAL, registered_proof as u64, &verifying_key)
+}
+
+/// Exports the verifying key of `registered_proof` from the parameter cache.
+pub fn export_post_verifying_key(registered_proof: RegisteredPoStProof) -> Result<Vec<u8>> {
+    ensure!(registered_proof.major_version() == 1, "only V1 supported");
+
+    let path = registered_proof.cache_verifying_key_path()?;
+    let verifying_key =
+        fs::read(&path).with_context(|| format!("could not read verifying key {:?}", path))?;
+
+    export_verifying_key(PROOF_KIND_POST, registered_proof as u64, &verifying_key)
+}
+
+/// Exports the public inputs of a seal proof, as computed by `seal::get_seal_inputs`.
+pub fn export_seal_inputs(
+    registered_proof: RegisteredSealProof,
+    comm_r: Commitment,
+    comm_d: Commitment,
+    prover_id: ProverId,
+    sector_id: SectorId,
+    ticket: Ticket,
+    seed: Ticket,
+) -> Result<Vec<u8>> {
+    let inputs = seal::get_seal_inputs(
+        registered_proof,
+        comm_r,
+        comm_d,
+        prover_id,
+        sector_id,
+        ticket,
+        seed,
+    )?;
+    let inputs_per_partition = inputs.first().map(Vec::len).unwrap_or(0);
+    ensure!(
+        inputs
+            .iter()
+            .all(|partition| partition.len() == inputs_per_partition),
+        "partitions have different numbers of public inputs"
+    );
+
+    let mut out = Vec::with_capacity(HEADER_LEN + 8 + inputs.len() * inputs_per_partition * 32);
+    write_header(
+        &mut out,
+        PUBLIC_INPUTS_MAGIC,
+        PROOF_KIND_SEAL,
+        registered_proof as u64,
+    );
+    out.extend_from_slice(&(inputs.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(inputs_per_partition as u32).to_le_bytes());
+    for input in inputs.iter().flatten() {
+        out.extend_from_slice(&fr32::fr_into_bytes(input));
+    }
+
+    Ok(out)
+}
+
+fn export_verifying_key(
+    kind: u8,
+    registered_proof_id: u64,
+    verifying_key: &[u8],
+) -> Result<Vec<u8>> {
+    // Reading the key checks that it is well formed and gives the number of public inputs,
+    // the first `ic` point is not tied to an input.
+    let parsed =
+        groth16::VerifyingKey::<Bls12>::read(verifying_key).context("invalid verifying key")?;
+    ensure!(!parsed.ic.is_empty(), "verifying key has no ic points");
+
+    let mut out = Vec::with_capacity(HEADER_LEN + 8 + verifying_key.len());
+    write_header(&mut out, VERIFYING_KEY_MAGIC, kind, registered_proof_id);
+    out.extend_from_slice(&(parsed.ic.len() as u32 - 1).to_le_bytes());
+    out.extend_from_slice(&(verifying_key.len() as u32).to_le_bytes());
+    out.extend_from_slice(verifying_key);
+
+    Ok(out)
+}
+
+fn write_header(out: &mut Vec<u8>, magic: &[u8; 4], kind: u8, registered_proof_id: u64) {
+    out.extend_from_slice(magic);
+    out.push(FORMAT_VERSION);
+    out.push(CURVE_BLS12_381);
+    out.push(kind);
+    out.extend_from_slice(&registered_proof_id.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bellperson::bls::{G1Affine, G2Affine};
+    use groupy::CurveAffine;
+
+    const VERIFYING_KEY_FIXTURE: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/verifying_key_2kib_v1_1.bin"
+    ));
+
+    fn generator_verifying_key() -> groth16::VerifyingKey<Bls12> {
+        groth16::VerifyingKey {
+            alpha_g1: G1Affine::one(),
+            beta_g1: G1Affine::one(),
+            beta_g2: G2Affine::one(),
+            gamma_g2: G2Affine::one(),
+            delta_g1: G1Affine::one(),
+            delta_g2: G2Affine::one(),
+            ic: vec![G1Affine::one(); 3],
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        let mut out = Vec::new();
+        write_header(
+            &mut out,
+            VERIFYING_KEY_MAGIC,
+            PROOF_KIND_POST,
+            RegisteredPoStProof::StackedDrgWindow32GiBV1 as u64,
+        );
+        assert_eq!(
+            out,
+            [b'F', b'P', b'V', b'K', 1, 1, 1, 8, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_export_seal_inputs() {
+        let registered_proof = RegisteredSealProof::StackedDrg2KiBV1_1;
+        let comm_r = [1u8; 32];
+        let comm_d = [2u8; 32];
+        let prover_id = [3u8; 32];
+        let sector_id = SectorId::from(4);
+        let ticket = [5u8; 32];
+        let seed = [6u8; 32];
+
+        let inputs = seal::get_seal_inputs(
+            registered_proof,
+            comm_r,
+            comm_d,
+            prover_id,
+            sector_id,
+            ticket,
+            seed,
+        )
+        .expect("failed to get seal inputs");
+        let exported = export_seal_inputs(
+            registered_proof,
+            comm_r,
+            comm_d,
+            prover_id,
+            sector_id,
+            ticket,
+            seed,
+        )
+        .expect("failed to export seal inputs");
+
+        assert_eq!(&exported[..4], PUBLIC_INPUTS_MAGIC);
+        assert_eq!(
+            &exported[4..7],
+            &[FORMAT_VERSION, CURVE_BLS12_381, PROOF_KIND_SEAL]
+        );
+        assert_eq!(&exported[7..15], &5u64.to_le_bytes());
+
+        let partitions =
+            u32::from_le_bytes([exported[15], exported[16], exported[17], exported[18]]);
+        let per_partition =
+            u32::from_le_bytes([exported[19], exported[20], exported[21], exported[22]]);
+        assert_eq!(partitions as usize, inputs.len());
+        assert_eq!(per_partition as usize, inputs[0].len());
+
+        let scalars: Vec<_> = exported[23..]
+            .chunks(32)
+            .map(|chunk| fr32::bytes_into_fr(chunk).expect("invalid scalar"))
+            .collect();
+        let expected: Vec<_> = inputs.into_iter().flatten().collect();
+        assert_eq!(scalars, expected);
+    }
+
+    #[test]
+    fn test_export_verifying_key() {
+        let mut verifying_key = Vec::new();
+        generator_verifying_key()
+            .write(&mut verifying_key)
+            .expect("failed to write verifying key");
+        // Three uncompressed G1 and G2 points each, the ic count and three ic points.
+        assert_eq!(verifying_key.len(), 3 * 96 + 3 * 192 + 4 + 3 * 96);
+
+        let exported = export_verifying_key(
+            PROOF_KIND_SEAL,
+            RegisteredSealProof::StackedDrg2KiBV1_1 as u64,
+            &verifying_key,
+        )
+        .expect("failed to export verifying key");
+
+        assert_eq!(
+            &exported[..HEADER_LEN],
+            &[b'F', b'P', b'V', b'K', 1, 1, 0, 5, 0, 0, 0, 0, 0, 0, 0]
+        );
+        // The first ic point is not tied to an input.
+        assert_eq!(&exported[15..19], &2u32.to_le_bytes());
+        assert_eq!(
+            &exported[19..23],
+            &(verifying_key.len() as u32).to_le_bytes()
+        );
+        assert_eq!(&exported[23..], &verifying_key[..]);
+
+        assert_eq!(&exported[..], VERIFYING_KEY_FIXTURE);
+    }
+
+    #[test]
+    fn test_export_invalid_verifying_key() {
+        let mut verifying_key = Vec::new();
+        generator_verifying_key()
+            .write(&mut verifying_key)
+            .expect("failed to write verifying key");
+
+        let truncated = &verifying_key[..verifying_key.len() - 1];
+        assert!(export_verifying_key(PROOF_KIND_SEAL, 5, truncated).is_err());
+
+        // Flip a bit in the x coordinate of alpha_g1, so it is no longer on the curve.
+        verifying_key[47] ^= 1;
+        assert!(export_verifying_key(PROOF_KIND_SEAL, 5, &verifying_key).is_err());
+    }
+}
@@ -4,13 +4,21 @@
 //#![warn(clippy::unwrap_used)]
 #![allow(clippy::upper_case_acronyms)]
 
+pub mod export;
+pub mod piece;
 pub mod post;
+pub mod preflight;
+pub mod scrub;
 pub mod seal;
+pub mod verification_cache;
 
 mod registry;
 mod types;
 
-pub use crate::registry::{RegisteredAggregationProof, RegisteredPoStProof, RegisteredSealProof};
+pub use crate::registry::{
+    RegisteredAggregationProof, RegisteredPoStProof, RegisteredSealProof, REGISTERED_POST_PROOFS,
+    REGISTERED_SEAL_PROOFS,
+};
 pub use crate::types::{PrivateReplicaInfo, PublicReplicaInfo};
 
 pub use filecoin_proofs_v1::types::{
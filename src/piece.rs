@@ -0,0 +1,288 @@
+//! Conversions between deal-level piece CIDs and the piece commitments (comm_p) used by the
+//! proofs.
+//!
+//! A piece CID is a CIDv1 with the `fil-commitment-unsealed` codec and a
+//! `sha2-256-trunc254-padded` multihash whose digest is the 32 byte comm_p.
+
+use anyhow::{bail, ensure, Context, Result};
+
+use crate::{Commitment, PaddedBytesAmount, PieceInfo, UnpaddedBytesAmount};
+
+const CID_VERSION: u64 = 1;
+/// Multicodec `fil-commitment-unsealed`.
+const FIL_COMMITMENT_UNSEALED: u64 = 0xf101;
+/// Multihash `sha2-256-trunc254-padded`.
+const SHA2_256_TRUNC254_PADDED: u64 = 0x1012;
+/// Multibase prefix of lowercase, unpadded RFC 4648 base32.
+const BASE32_PREFIX: char = 'b';
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// The smallest piece the proofs accept.
+const MIN_PADDED_PIECE_SIZE: u64 = 128;
+
+/// Encodes `comm_p` as a binary piece CID.
+pub fn comm_p_to_piece_cid(comm_p: &Commitment) -> Vec<u8> {
+    let mut cid = Vec::with_capacity(39);
+    write_varint(&mut cid, CID_VERSION);
+    write_varint(&mut cid, FIL_COMMITMENT_UNSEALED);
+    write_varint(&mut cid, SHA2_256_TRUNC254_PADDED);
+    write_varint(&mut cid, comm_p.len() as u64);
+    cid.extend_from_slice(comm_p);
+    cid
+}
+
+/// Decodes a binary piece CID into its `comm_p`.
+pub fn piece_cid_to_comm_p(cid: &[u8]) -> Result<Commitment> {
+    let mut bytes = cid;
+
+    let version = read_varint(&mut bytes)?;
+    ensure!(
+        version == CID_VERSION,
+        "unsupported cid version {}",
+        version
+    );
+
+    let codec = read_varint(&mut bytes)?;
+    ensure!(
+        codec == FIL_COMMITMENT_UNSEALED,
+        "cid codec {:#x} is not fil-commitment-unsealed",
+        codec
+    );
+
+    let hash = read_varint(&mut bytes)?;
+    ensure!(
+        hash == SHA2_256_TRUNC254_PADDED,
+        "multihash {:#x} is not sha2-256-trunc254-padded",
+        hash
+    );
+
+    let mut comm_p = [0u8; 32];
+    let len = read_varint(&mut bytes)?;
+    ensure!(
+        len == comm_p.len() as u64 && bytes.len() == comm_p.len(),
+        "invalid commitment length"
+    );
+    comm_p.copy_from_slice(bytes);
+    // sha2-256-trunc254-padded digests have their two most significant bits cleared.
+    ensure!(
+        comm_p[31] & 0xc0 == 0,
+        "commitment is not a sha2-256-trunc254-padded digest"
+    );
+
+    Ok(comm_p)
+}
+
+/// Encodes `comm_p` as a piece CID string, e.g. `baga6ea4seaq...`.
+pub fn comm_p_to_piece_cid_string(comm_p: &Commitment) -> String {
+    let cid = comm_p_to_piece_cid(comm_p);
+
+    let mut encoded = String::with_capacity(1 + (cid.len() * 8 + 4) / 5);
+    encoded.push(BASE32_PREFIX);
+    encoded.push_str(&base32_encode(&cid));
+    encoded
+}
+
+/// Decodes a piece CID string into its `comm_p`.
+pub fn piece_cid_string_to_comm_p(cid: &str) -> Result<Commitment> {
+    ensure!(
+        cid.starts_with(BASE32_PREFIX),
+        "only base32 encoded piece cids are supported"
+    );
+
+    let bytes = base32_decode(&cid[BASE32_PREFIX.len_utf8()..]).context("invalid piece cid")?;
+    piece_cid_to_comm_p(&bytes)
+}
+
+/// Converts the padded piece size used in deals into the unpadded size used by the proofs.
+pub fn unpadded_piece_size(padded_size: PaddedBytesAmount) -> Result<UnpaddedBytesAmount> {
+    let size = u64::from(padded_size);
+    ensure!(
+        size >= MIN_PADDED_PIECE_SIZE,
+        "padded piece size {} is smaller than {}",
+        size,
+        MIN_PADDED_PIECE_SIZE
+    );
+    ensure!(
+        size.is_power_of_two(),
+        "padded piece size {} is not a power of two",
+        size
+    );
+
+    Ok(UnpaddedBytesAmount::from(padded_size))
+}
+
+/// Converts the unpadded piece size used by the proofs into the padded size used in deals.
+pub fn padded_piece_size(unpadded_size: UnpaddedBytesAmount) -> Result<PaddedBytesAmount> {
+    let padded_size = PaddedBytesAmount::from(unpadded_size);
+    ensure!(
+        UnpaddedBytesAmount::from(padded_size) == unpadded_size,
+        "unpadded piece size {} is not a multiple of 127",
+        u64::from(unpadded_size)
+    );
+    unpadded_piece_size(padded_size)?;
+
+    Ok(padded_size)
+}
+
+/// Builds the `PieceInfo` for a deal's piece CID and padded piece size.
+pub fn piece_info_from_cid(cid: &[u8], padded_size: PaddedBytesAmount) -> Result<PieceInfo> {
+    let comm_p = piece_cid_to_comm_p(cid)?;
+    let size = unpadded_piece_size(padded_size)?;
+
+    PieceInfo::new(comm_p, size)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    // A u64 takes at most 10 bytes.
+    for i in 0..10 {
+        let (byte, rest) = bytes.split_first().context("unexpected end of cid")?;
+        *bytes = rest;
+
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    bail!("varint overflow")
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    encoded
+}
+
+fn base32_decode(encoded: &str) -> Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in encoded.bytes() {
+        let value = match BASE32_ALPHABET.iter().position(|a| *a == c) {
+            Some(value) => value as u32,
+            None => bail!("invalid base32 character {:?}", c as char),
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+
+    // Unpadded base32 leaves at most 4 bits, which must be zero, so every byte string has a
+    // single encoding.
+    ensure!(bits < 5, "invalid base32 length {}", encoded.len());
+    ensure!(
+        buffer & ((1 << bits) - 1) == 0,
+        "non-zero trailing bits in base32"
+    );
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_cid_roundtrip() {
+        let mut comm_p = [0u8; 32];
+        for (i, b) in comm_p.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+
+        let cid = comm_p_to_piece_cid(&comm_p);
+        assert_eq!(&cid[..7], &[0x01, 0x81, 0xe2, 0x03, 0x92, 0x20, 0x20]);
+        assert_eq!(
+            piece_cid_to_comm_p(&cid).expect("failed to decode cid"),
+            comm_p
+        );
+
+        let cid_string = comm_p_to_piece_cid_string(&comm_p);
+        assert_eq!(
+            cid_string,
+            "baga6ea4seaqaaaicamcakbqhbaequcymbuha6earcijrifiwc4mbsgq3dqor4hy"
+        );
+        assert_eq!(
+            piece_cid_string_to_comm_p(&cid_string).expect("failed to decode cid string"),
+            comm_p
+        );
+    }
+
+    #[test]
+    fn test_invalid_piece_cid() {
+        let comm_p = [1u8; 32];
+
+        let mut cid = comm_p_to_piece_cid(&comm_p);
+        // fil-commitment-sealed instead of unsealed
+        cid[1] = 0x82;
+        assert!(piece_cid_to_comm_p(&cid).is_err());
+
+        let cid = comm_p_to_piece_cid(&comm_p);
+        assert!(piece_cid_to_comm_p(&cid[..cid.len() - 1]).is_err());
+        assert!(piece_cid_string_to_comm_p("zaga6ea4seaq").is_err());
+        assert!(piece_cid_string_to_comm_p("baga6ea4seaq!").is_err());
+
+        // Not a trunc254 digest.
+        let cid = comm_p_to_piece_cid(&[0xff; 32]);
+        assert!(piece_cid_to_comm_p(&cid).is_err());
+    }
+
+    #[test]
+    fn test_base32_canonical() {
+        let cid_string = "baga6ea4seaqaaaicamcakbqhbaequcymbuha6earcijrifiwc4mbsgq3dqor4hy";
+        assert!(piece_cid_string_to_comm_p(cid_string).is_ok());
+
+        // 'z' sets one of the three trailing bits of 'y'.
+        let mut non_canonical = cid_string[..cid_string.len() - 1].to_string();
+        non_canonical.push('z');
+        assert!(piece_cid_string_to_comm_p(&non_canonical).is_err());
+
+        assert_eq!(base32_decode("ma").expect("valid base32"), vec![0x60]);
+        assert!(base32_decode("mb").is_err());
+        assert!(base32_decode("a").is_err());
+        assert!(base32_decode("aaa").is_err());
+    }
+
+    #[test]
+    fn test_piece_sizes() {
+        assert_eq!(
+            unpadded_piece_size(PaddedBytesAmount(2048)).expect("invalid padded size"),
+            UnpaddedBytesAmount(2032)
+        );
+        assert_eq!(
+            padded_piece_size(UnpaddedBytesAmount(2032)).expect("invalid unpadded size"),
+            PaddedBytesAmount(2048)
+        );
+
+        assert!(unpadded_piece_size(PaddedBytesAmount(64)).is_err());
+        assert!(unpadded_piece_size(PaddedBytesAmount(3072)).is_err());
+        assert!(padded_piece_size(UnpaddedBytesAmount(2000)).is_err());
+        assert!(padded_piece_size(UnpaddedBytesAmount(127 * 3)).is_err());
+    }
+}
@@ -3,12 +3,40 @@ use std::collections::BTreeMap;
 use anyhow::{ensure, Result};
 use filecoin_proofs_v1::with_shape;
 
+use crate::seal::prevalidate_groth_proofs;
 use crate::types::VanillaProofBytes;
 use crate::{
     ChallengeSeed, FallbackPoStSectorProof, MerkleTreeTrait, PoStType, PrivateReplicaInfo,
     ProverId, PublicReplicaInfo, RegisteredPoStProof, SectorId, SnarkProof,
 };
 
+/// Loads the Groth parameters and verifying keys for the given proofs into the in-memory
+/// parameter cache, so the first PoSt does not pay for loading them from disk.
+pub fn warm_up(registered_proofs: &[RegisteredPoStProof]) -> Result<()> {
+    for registered_proof in registered_proofs {
+        ensure!(registered_proof.major_version() == 1, "only V1 supported");
+
+        with_shape!(
+            u64::from(registered_proof.sector_size()),
+            warm_up_inner,
+            *registered_proof,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn warm_up_inner<Tree: 'static + MerkleTreeTrait>(
+    registered_proof: RegisteredPoStProof,
+) -> Result<()> {
+    let config = registered_proof.as_v1_config();
+
+    filecoin_proofs_v1::caches::get_post_params::<Tree>(&config)?;
+    filecoin_proofs_v1::caches::get_post_verifying_key::<Tree>(&config)?;
+
+    Ok(())
+}
+
 pub fn generate_winning_post_sector_challenge(
     proof_type: RegisteredPoStProof,
     randomness: &ChallengeSeed,
@@ -246,6 +274,40 @@ fn generate_winning_post_inner<Tree: 'static + MerkleTreeTrait>(
     Ok(vec![(registered_proof_v1, posts_v1)])
 }
 
+/// Cheap structural checks on a PoSt proof over `num_sectors` sectors, without any pairings.
+///
+/// Checks that the proof has exactly one partition proof per partition needed for
+/// `num_sectors`, before parsing any curve points, and that every partition proof consists of
+/// valid curve points. Winning PoSt proofs always have a single partition. A proof passing this
+/// check may still be invalid; use `verify_winning_post` or `verify_window_post` for the full
+/// check.
+pub fn prevalidate_post_proof(
+    registered_proof: RegisteredPoStProof,
+    proof: &[u8],
+    num_sectors: usize,
+) -> Result<()> {
+    ensure!(registered_proof.major_version() == 1, "only V1 supported");
+    ensure!(num_sectors > 0, "no sectors supplied");
+
+    let partitions = match registered_proof.typ() {
+        PoStType::Winning => 1,
+        PoStType::Window => {
+            let sector_count = registered_proof.sector_count();
+            (num_sectors + sector_count - 1) / sector_count
+        }
+    };
+    let single_partition_proof_len = registered_proof.single_partition_proof_len();
+    let expected_len = partitions * single_partition_proof_len;
+    ensure!(
+        proof.len() == expected_len,
+        "invalid proof length {}, expected {}",
+        proof.len(),
+        expected_len
+    );
+
+    prevalidate_groth_proofs(proof, single_partition_proof_len)
+}
+
 pub fn verify_winning_post(
     randomness: &ChallengeSeed,
     proof: &[u8],
@@ -493,3 +555,63 @@ fn verify_window_post_inner<Tree: 'static + MerkleTreeTrait>(
 
     Ok(valid_v1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use filecoin_proofs_v1::SINGLE_PARTITION_PROOF_LEN;
+
+    #[test]
+    fn test_prevalidate_winning_post_proof() {
+        let registered_proof = RegisteredPoStProof::StackedDrgWinning2KiBV1;
+
+        let proof = vec![0u8; 2 * SINGLE_PARTITION_PROOF_LEN];
+        let err = prevalidate_post_proof(registered_proof, &proof, 1)
+            .expect_err("winning proof with two partitions");
+        assert!(err.to_string().contains("invalid proof length"));
+
+        assert!(prevalidate_post_proof(registered_proof, &[], 1).is_err());
+    }
+
+    #[test]
+    fn test_prevalidate_window_post_proof() {
+        let registered_proof = RegisteredPoStProof::StackedDrgWindow2KiBV1;
+        let sector_count = registered_proof.sector_count();
+
+        let err = prevalidate_post_proof(registered_proof, &[], 1).expect_err("empty proof");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "invalid proof length 0, expected {}",
+                SINGLE_PARTITION_PROOF_LEN
+            )
+        );
+
+        assert!(prevalidate_post_proof(registered_proof, &[], 0).is_err());
+
+        let proof = vec![0u8; 2 * SINGLE_PARTITION_PROOF_LEN - 1];
+        let err = prevalidate_post_proof(registered_proof, &proof, sector_count + 1)
+            .expect_err("proof length is not a multiple");
+        assert!(err.to_string().contains("invalid proof length"));
+
+        // More partitions than the sectors need are rejected before any point is parsed.
+        let proof = vec![0u8; 3 * SINGLE_PARTITION_PROOF_LEN];
+        let err = prevalidate_post_proof(registered_proof, &proof, sector_count + 1)
+            .expect_err("too many partitions");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "invalid proof length {}, expected {}",
+                3 * SINGLE_PARTITION_PROOF_LEN,
+                2 * SINGLE_PARTITION_PROOF_LEN
+            )
+        );
+
+        // Neither partition proof is a valid compressed curve point.
+        let proof = vec![0xffu8; 2 * SINGLE_PARTITION_PROOF_LEN];
+        let err = prevalidate_post_proof(registered_proof, &proof, sector_count + 1)
+            .expect_err("garbage proof");
+        assert_eq!(err.to_string(), "invalid partition proof 0");
+    }
+}
@@ -0,0 +1,373 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use filecoin_proofs_v1::constants;
+use storage_proofs_core::parameter_cache::VERSION;
+use storage_proofs_core::settings::SETTINGS;
+
+use crate::{get_parameter_data, get_verifying_key_data, RegisteredSealProof, NODE_SIZE};
+
+/// Status of a single Groth parameter or verifying key file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterFileStatus {
+    /// Location of the file in the parameter cache.
+    pub path: PathBuf,
+    /// Whether the file is present.
+    pub exists: bool,
+    /// Whether the file matches its published digest, `None` if the file is missing
+    /// or no digest is published for it.
+    pub digest_matches: Option<bool>,
+}
+
+impl ParameterFileStatus {
+    pub fn is_ok(&self) -> bool {
+        self.exists && self.digest_matches != Some(false)
+    }
+}
+
+/// The result of `preflight`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreflightReport {
+    pub registered_proof: RegisteredSealProof,
+    /// Parameter and verifying key files for the seal proof and its winning and window PoSt.
+    pub parameter_files: Vec<ParameterFileStatus>,
+    /// Upper bound of the disk space needed in the working directory to seal a single sector.
+    pub required_disk_space: u64,
+    /// Disk space available in the working directory.
+    pub available_disk_space: u64,
+    /// Location of the SDR parent cache, as configured by `FIL_PROOFS_PARENT_CACHE`.
+    pub parent_cache_dir: PathBuf,
+    /// Size of the parent cache file for this sector size, written by the first seal.
+    pub parent_cache_size: u64,
+    /// Whether a parent cache file of that size is already present.
+    pub parent_cache_exists: bool,
+    /// Disk space available in the parent cache directory.
+    pub parent_cache_available_space: u64,
+    /// Total memory, `None` if it could not be determined.
+    pub total_memory: Option<u64>,
+    /// Memory available for new allocations, `None` if it could not be determined.
+    pub available_memory: Option<u64>,
+    /// GPUs are not detected, so this is always `None`.
+    pub gpu_available: Option<bool>,
+}
+
+impl PreflightReport {
+    /// Returns true if all parameter files are valid and there is enough disk space to seal,
+    /// including the parent cache if it has not been written yet. Memory and GPUs are only
+    /// reported and not taken into account.
+    pub fn is_ready(&self) -> bool {
+        self.parameter_files.iter().all(ParameterFileStatus::is_ok)
+            && self.available_disk_space >= self.required_disk_space
+            && (self.parent_cache_exists
+                || self.parent_cache_available_space >= self.parent_cache_size)
+    }
+}
+
+/// Checks that the environment is able to seal and prove sectors of the given proof type.
+///
+/// This verifies that all parameter files are present and match their published digests,
+/// and that `work_dir` and the parent cache have enough space left to seal a sector. If both
+/// are on the same file system, the space needed adds up. Hashing the parameter files reads
+/// them in full, so this can take a while for the larger sector sizes.
+///
+/// Memory is reported but not checked against any requirement, and GPUs are not detected, so
+/// sealing may still fail for lack of either.
+pub fn preflight<P: AsRef<Path>>(
+    registered_proof: RegisteredSealProof,
+    work_dir: P,
+) -> Result<PreflightReport> {
+    let mut parameter_files = Vec::new();
+
+    check_parameters(
+        &registered_proof.circuit_identifier()?,
+        registered_proof.cache_params_path()?,
+        registered_proof.cache_verifying_key_path()?,
+        &mut parameter_files,
+    )?;
+
+    for post_proof in &[
+        registered_proof.into_winning_post(),
+        registered_proof.into_window_post(),
+    ] {
+        check_parameters(
+            &post_proof.circuit_identifier()?,
+            post_proof.cache_params_path()?,
+            post_proof.cache_verifying_key_path()?,
+            &mut parameter_files,
+        )?;
+    }
+
+    let available_disk_space = fs2::available_space(work_dir.as_ref()).with_context(|| {
+        format!(
+            "could not determine available space in {:?}",
+            work_dir.as_ref()
+        )
+    })?;
+
+    let parent_cache_dir = PathBuf::from(&SETTINGS.parent_cache);
+    let parent_cache_size = parent_cache_size(registered_proof);
+    let parent_cache_exists = parent_cache_exists(&parent_cache_dir, parent_cache_size);
+    let parent_cache_available_space = available_space(&parent_cache_dir)?;
+
+    let meminfo = fs::read_to_string("/proc/meminfo").ok();
+    let total_memory = meminfo
+        .as_ref()
+        .and_then(|meminfo| parse_meminfo(meminfo, "MemTotal"));
+    let available_memory = meminfo
+        .as_ref()
+        .and_then(|meminfo| parse_meminfo(meminfo, "MemAvailable"));
+
+    Ok(PreflightReport {
+        registered_proof,
+        parameter_files,
+        required_disk_space: required_disk_space(registered_proof),
+        available_disk_space,
+        parent_cache_dir,
+        parent_cache_size,
+        parent_cache_exists,
+        parent_cache_available_space,
+        total_memory,
+        available_memory,
+        gpu_available: None,
+    })
+}
+
+/// Staged sector, replica, one label file per layer and the trees in the cache directory.
+fn required_disk_space(registered_proof: RegisteredSealProof) -> u64 {
+    let sector_size = u64::from(registered_proof.sector_size());
+    let layers = *constants::LAYERS
+        .read()
+        .expect("layers read error")
+        .get(&sector_size)
+        .expect("invalid sector size") as u64;
+
+    // tree_c and tree_r_last are octrees over the sector's nodes, so each takes at most 8/7
+    // of the sector size; tree_r_last is usually much smaller as its lower rows are discarded.
+    let octree_size = (sector_size * 8 + 6) / 7;
+
+    // staged + sealed + labels + tree_d (binary tree, 2x) + tree_c + tree_r_last
+    sector_size * (layers + 4) + 2 * octree_size
+}
+
+/// One u32 parent index per base and expansion parent of every node.
+fn parent_cache_size(registered_proof: RegisteredSealProof) -> u64 {
+    let nodes = u64::from(registered_proof.sector_size()) / NODE_SIZE as u64;
+    nodes * (constants::DRG_DEGREE + constants::EXP_DEGREE) as u64 * 4
+}
+
+/// The file name of a parent cache depends on the graph, so look for any parent cache file of
+/// the expected size.
+fn parent_cache_exists(dir: &Path, size: u64) -> bool {
+    let prefix = format!("v{}-sdr-parent-", VERSION);
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+
+    entries.filter_map(|entry| entry.ok()).any(|entry| {
+        entry.file_name().to_string_lossy().starts_with(&prefix)
+            && entry.metadata().map(|m| m.len() == size).unwrap_or(false)
+    })
+}
+
+/// Space available for `path`, which is created on demand and may not exist yet.
+fn available_space(path: &Path) -> Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or_else(|| Path::new("/"));
+
+    fs2::available_space(existing)
+        .with_context(|| format!("could not determine available space in {:?}", existing))
+}
+
+/// Reads a value from `/proc/meminfo`, which is given in kB.
+fn parse_meminfo(meminfo: &str, key: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        if parts.next()?.strip_suffix(':')? != key {
+            return None;
+        }
+        let value: u64 = parts.next()?.parse().ok()?;
+        match parts.next() {
+            Some("kB") => Some(value * 1024),
+            None => Some(value),
+            Some(_) => None,
+        }
+    })
+}
+
+fn check_parameters(
+    id: &str,
+    params_path: PathBuf,
+    verifying_key_path: PathBuf,
+    statuses: &mut Vec<ParameterFileStatus>,
+) -> Result<()> {
+    let params_digest = get_parameter_data(id).map(|data| data.digest.as_str());
+    statuses.push(check_file(params_path, params_digest)?);
+
+    let verifying_key_digest = get_verifying_key_data(id).map(|data| data.digest.as_str());
+    statuses.push(check_file(verifying_key_path, verifying_key_digest)?);
+
+    Ok(())
+}
+
+fn check_file(path: PathBuf, expected_digest: Option<&str>) -> Result<ParameterFileStatus> {
+    if !path.exists() {
+        return Ok(ParameterFileStatus {
+            path,
+            exists: false,
+            digest_matches: None,
+        });
+    }
+
+    let digest_matches = match expected_digest {
+        Some(expected) => Some(parameter_file_digest(&path)? == expected),
+        None => None,
+    };
+
+    Ok(ParameterFileStatus {
+        path,
+        exists: true,
+        digest_matches,
+    })
+}
+
+/// Computes the digest of a parameter file in the format used by the published parameter
+/// manifest.
+pub fn parameter_file_digest<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("could not open {:?}", path))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = blake2b_simd::State::new();
+    io::copy(&mut reader, &mut hasher)?;
+
+    Ok(hasher.finalize().to_hex()[..32].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[test]
+    fn test_parameter_file_digest() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("v28-test.params");
+        fs::write(&path, b"filecoin parameters").expect("failed to write file");
+
+        let digest = parameter_file_digest(&path).expect("failed to hash file");
+        assert_eq!(digest, "37cfc5b40bb919aac0774b5bfaa20a2d");
+    }
+
+    #[test]
+    fn test_check_file() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("v28-test.params");
+
+        let status = check_file(path.clone(), Some("37cfc5b40bb919aac0774b5bfaa20a2d"))
+            .expect("failed to check file");
+        assert!(!status.exists);
+        assert_eq!(status.digest_matches, None);
+        assert!(!status.is_ok());
+
+        fs::write(&path, b"filecoin parameters").expect("failed to write file");
+        let status = check_file(path.clone(), Some("37cfc5b40bb919aac0774b5bfaa20a2d"))
+            .expect("failed to check file");
+        assert_eq!(status.digest_matches, Some(true));
+        assert!(status.is_ok());
+
+        let status = check_file(path.clone(), Some("00000000000000000000000000000000"))
+            .expect("failed to check file");
+        assert_eq!(status.digest_matches, Some(false));
+        assert!(!status.is_ok());
+
+        let status = check_file(path, None).expect("failed to check file");
+        assert_eq!(status.digest_matches, None);
+        assert!(status.is_ok());
+    }
+
+    #[test]
+    fn test_is_ready() {
+        let file = ParameterFileStatus {
+            path: PathBuf::from("v28-test.params"),
+            exists: true,
+            digest_matches: Some(true),
+        };
+        let mut report = PreflightReport {
+            registered_proof: RegisteredSealProof::StackedDrg2KiBV1_1,
+            parameter_files: vec![file.clone()],
+            required_disk_space: 1024,
+            available_disk_space: 1024,
+            parent_cache_dir: PathBuf::from("/var/tmp/filecoin-parents"),
+            parent_cache_size: 512,
+            parent_cache_exists: false,
+            parent_cache_available_space: 512,
+            total_memory: None,
+            available_memory: None,
+            gpu_available: None,
+        };
+        assert!(report.is_ready());
+
+        report.available_disk_space = 1023;
+        assert!(!report.is_ready());
+
+        report.available_disk_space = 1024;
+        report.parent_cache_available_space = 511;
+        assert!(!report.is_ready());
+
+        report.parent_cache_exists = true;
+        assert!(report.is_ready());
+
+        report.parameter_files.push(ParameterFileStatus {
+            digest_matches: Some(false),
+            ..file
+        });
+        assert!(!report.is_ready());
+    }
+
+    #[test]
+    fn test_required_disk_space() {
+        // 11 layers of labels, the staged and sealed sector and the trees.
+        let sector_size = 32u64 << 30;
+        let required = required_disk_space(RegisteredSealProof::StackedDrg32GiBV1_1);
+        assert!(required > 16 * sector_size);
+        assert!(required < 18 * sector_size);
+    }
+
+    #[test]
+    fn test_parent_cache() {
+        // 2^30 nodes with 14 parents each.
+        assert_eq!(
+            parent_cache_size(RegisteredSealProof::StackedDrg32GiBV1_1),
+            56 << 30
+        );
+
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let size = parent_cache_size(RegisteredSealProof::StackedDrg2KiBV1_1);
+        assert!(!parent_cache_exists(dir.path(), size));
+
+        let name = format!("v{}-sdr-parent-test.cache", VERSION);
+        fs::write(dir.path().join(&name), vec![0u8; size as usize - 1])
+            .expect("failed to write file");
+        assert!(!parent_cache_exists(dir.path(), size));
+
+        fs::write(dir.path().join(&name), vec![0u8; size as usize]).expect("failed to write file");
+        assert!(parent_cache_exists(dir.path(), size));
+
+        available_space(&dir.path().join("missing").join("parents"))
+            .expect("failed to get available space");
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16318480 kB\nMemFree:         1021164 kB\nMemAvailable:    8543412 kB\nHugePages_Total:       0\n";
+        assert_eq!(parse_meminfo(meminfo, "MemTotal"), Some(16318480 * 1024));
+        assert_eq!(parse_meminfo(meminfo, "MemAvailable"), Some(8543412 * 1024));
+        assert_eq!(parse_meminfo(meminfo, "HugePages_Total"), Some(0));
+        assert_eq!(parse_meminfo(meminfo, "SwapTotal"), None);
+    }
+}
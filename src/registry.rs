@@ -24,6 +24,20 @@ pub enum RegisteredSealProof {
     StackedDrg64GiBV1_1,
 }
 
+/// Every `RegisteredSealProof`, in declaration order.
+pub const REGISTERED_SEAL_PROOFS: &[RegisteredSealProof] = &[
+    RegisteredSealProof::StackedDrg2KiBV1,
+    RegisteredSealProof::StackedDrg8MiBV1,
+    RegisteredSealProof::StackedDrg512MiBV1,
+    RegisteredSealProof::StackedDrg32GiBV1,
+    RegisteredSealProof::StackedDrg64GiBV1,
+    RegisteredSealProof::StackedDrg2KiBV1_1,
+    RegisteredSealProof::StackedDrg8MiBV1_1,
+    RegisteredSealProof::StackedDrg512MiBV1_1,
+    RegisteredSealProof::StackedDrg32GiBV1_1,
+    RegisteredSealProof::StackedDrg64GiBV1_1,
+];
+
 /// Available aggregation proof types.
 /// Enum is append-only: once published, a `RegisteredAggregationProof` value must never change.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -268,6 +282,20 @@ pub enum RegisteredPoStProof {
     StackedDrgWindow64GiBV1,
 }
 
+/// Every `RegisteredPoStProof`, in declaration order.
+pub const REGISTERED_POST_PROOFS: &[RegisteredPoStProof] = &[
+    RegisteredPoStProof::StackedDrgWinning2KiBV1,
+    RegisteredPoStProof::StackedDrgWinning8MiBV1,
+    RegisteredPoStProof::StackedDrgWinning512MiBV1,
+    RegisteredPoStProof::StackedDrgWinning32GiBV1,
+    RegisteredPoStProof::StackedDrgWinning64GiBV1,
+    RegisteredPoStProof::StackedDrgWindow2KiBV1,
+    RegisteredPoStProof::StackedDrgWindow8MiBV1,
+    RegisteredPoStProof::StackedDrgWindow512MiBV1,
+    RegisteredPoStProof::StackedDrgWindow32GiBV1,
+    RegisteredPoStProof::StackedDrgWindow64GiBV1,
+];
+
 impl RegisteredPoStProof {
     /// Return the version for this proof.
     pub fn version(self) -> ApiVersion {
@@ -461,22 +489,36 @@ mod tests {
     use super::*;
     use filecoin_proofs_v1::MAX_LEGACY_REGISTERED_SEAL_PROOF_ID;
 
-    const REGISTERED_SEAL_PROOFS: [RegisteredSealProof; 10] = [
-        RegisteredSealProof::StackedDrg2KiBV1,
-        RegisteredSealProof::StackedDrg8MiBV1,
-        RegisteredSealProof::StackedDrg512MiBV1,
-        RegisteredSealProof::StackedDrg32GiBV1,
-        RegisteredSealProof::StackedDrg64GiBV1,
-        RegisteredSealProof::StackedDrg2KiBV1_1,
-        RegisteredSealProof::StackedDrg8MiBV1_1,
-        RegisteredSealProof::StackedDrg512MiBV1_1,
-        RegisteredSealProof::StackedDrg32GiBV1_1,
-        RegisteredSealProof::StackedDrg64GiBV1_1,
-    ];
+    /// Counts the variants of `T` by deserializing consecutive variant indices until one fails,
+    /// so that a variant missing from `REGISTERED_*_PROOFS` is noticed.
+    fn variant_count<T: serde::de::DeserializeOwned>() -> usize {
+        (0u32..)
+            .take_while(|i| bincode::deserialize::<T>(&i.to_le_bytes()).is_ok())
+            .count()
+    }
+
+    #[test]
+    fn test_registered_proofs_complete() {
+        assert_eq!(
+            variant_count::<RegisteredSealProof>(),
+            REGISTERED_SEAL_PROOFS.len()
+        );
+        for (i, rsp) in REGISTERED_SEAL_PROOFS.iter().enumerate() {
+            assert_eq!(*rsp as usize, i);
+        }
+
+        assert_eq!(
+            variant_count::<RegisteredPoStProof>(),
+            REGISTERED_POST_PROOFS.len()
+        );
+        for (i, rpp) in REGISTERED_POST_PROOFS.iter().enumerate() {
+            assert_eq!(*rpp as usize, i);
+        }
+    }
 
     #[test]
     fn test_porep_id() {
-        for rsp in &REGISTERED_SEAL_PROOFS {
+        for rsp in REGISTERED_SEAL_PROOFS {
             test_porep_id_aux(rsp);
         }
     }
@@ -525,7 +567,7 @@ mod tests {
 
     #[test]
     fn test_max_initial_porep_id() {
-        for rsp in &REGISTERED_SEAL_PROOFS {
+        for rsp in REGISTERED_SEAL_PROOFS {
             let mut porep_id_type_bytes = [0u8; 8];
             let porep_id = rsp.porep_id();
 
@@ -552,7 +594,7 @@ mod tests {
 
     #[test]
     fn test_verifying_key_path() {
-        for rsp in &REGISTERED_SEAL_PROOFS {
+        for rsp in REGISTERED_SEAL_PROOFS {
             rsp.cache_verifying_key_path()
                 .expect("failed to get verifying key path");
         }
@@ -560,7 +602,7 @@ mod tests {
 
     #[test]
     fn test_verifying_key_cid() {
-        for rsp in &REGISTERED_SEAL_PROOFS {
+        for rsp in REGISTERED_SEAL_PROOFS {
             rsp.verifying_key_cid()
                 .expect("failed to get verifying key cid");
         }
@@ -568,14 +610,14 @@ mod tests {
 
     #[test]
     fn test_params_path() {
-        for rsp in &REGISTERED_SEAL_PROOFS {
+        for rsp in REGISTERED_SEAL_PROOFS {
             rsp.cache_params_path().expect("failed to get params path");
         }
     }
 
     #[test]
     fn test_params_cid() {
-        for rsp in &REGISTERED_SEAL_PROOFS {
+        for rsp in REGISTERED_SEAL_PROOFS {
             rsp.params_cid().expect("failed to get params_cid");
         }
     }
@@ -0,0 +1,190 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use anyhow::{ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::RegisteredSealProof;
+
+/// Chunk size used by `generate_replica_manifest` if none is given.
+pub const DEFAULT_CHUNK_SIZE: u64 = 1 << 20;
+
+/// Checksums over fixed size chunks of a sealed replica.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicaManifest {
+    pub registered_proof: RegisteredSealProof,
+    pub chunk_size: u64,
+    pub replica_len: u64,
+    pub checksums: Vec<[u8; 32]>,
+}
+
+/// Computes the manifest for a freshly sealed replica, to be stored alongside it and passed to
+/// `scrub` later on. `chunk_size` defaults to `DEFAULT_CHUNK_SIZE`.
+pub fn generate_replica_manifest<P: AsRef<Path>>(
+    registered_proof: RegisteredSealProof,
+    replica_path: P,
+    chunk_size: Option<u64>,
+) -> Result<ReplicaManifest> {
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    ensure!(chunk_size > 0, "chunk size must be positive");
+
+    let (replica_len, checksums) = chunk_checksums(replica_path.as_ref(), chunk_size)?;
+    check_replica_len(registered_proof, replica_len)?;
+
+    Ok(ReplicaManifest {
+        registered_proof,
+        chunk_size,
+        replica_len,
+        checksums,
+    })
+}
+
+/// Re-reads the replica and returns the indices of all chunks that no longer match the manifest.
+pub fn scrub<P: AsRef<Path>>(replica_path: P, manifest: &ReplicaManifest) -> Result<Vec<u64>> {
+    ensure!(manifest.chunk_size > 0, "chunk size must be positive");
+    check_replica_len(manifest.registered_proof, manifest.replica_len)?;
+
+    let (replica_len, checksums) = chunk_checksums(replica_path.as_ref(), manifest.chunk_size)?;
+    ensure!(
+        replica_len == manifest.replica_len,
+        "replica length {} does not match manifest length {}",
+        replica_len,
+        manifest.replica_len
+    );
+    ensure!(
+        checksums.len() == manifest.checksums.len(),
+        "replica has {} chunks, manifest has {} checksums",
+        checksums.len(),
+        manifest.checksums.len()
+    );
+
+    Ok(checksums
+        .iter()
+        .zip(manifest.checksums.iter())
+        .enumerate()
+        .filter(|(_, (actual, expected))| actual != expected)
+        .map(|(i, _)| i as u64)
+        .collect())
+}
+
+/// A sealed replica is exactly one sector long.
+fn check_replica_len(registered_proof: RegisteredSealProof, replica_len: u64) -> Result<()> {
+    let sector_size = u64::from(registered_proof.sector_size());
+    ensure!(
+        replica_len == sector_size,
+        "replica length {} does not match sector size {}",
+        replica_len,
+        sector_size
+    );
+
+    Ok(())
+}
+
+fn chunk_checksums(path: &Path, chunk_size: u64) -> Result<(u64, Vec<[u8; 32]>)> {
+    let file = File::open(path).with_context(|| format!("could not open replica {:?}", path))?;
+    let mut reader = BufReader::new(file);
+
+    let mut len = 0;
+    let mut checksums = Vec::new();
+    loop {
+        let mut hasher = blake2b_simd::Params::new().hash_length(32).to_state();
+        let read = io::copy(&mut (&mut reader).take(chunk_size), &mut hasher)?;
+        if read == 0 {
+            break;
+        }
+        len += read;
+
+        let mut checksum = [0u8; 32];
+        checksum.copy_from_slice(hasher.finalize().as_bytes());
+        checksums.push(checksum);
+    }
+
+    Ok((len, checksums))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+
+    const REGISTERED_PROOF: RegisteredSealProof = RegisteredSealProof::StackedDrg2KiBV1_1;
+
+    fn replica() -> tempfile::NamedTempFile {
+        let data: Vec<u8> = (0..2048).map(|i| i as u8).collect();
+
+        let mut replica = tempfile::NamedTempFile::new().expect("failed to create replica");
+        replica.write_all(&data).expect("failed to write replica");
+        replica
+    }
+
+    #[test]
+    fn test_scrub() {
+        let chunk_size = 512;
+        let replica = replica();
+
+        let manifest =
+            generate_replica_manifest(REGISTERED_PROOF, replica.path(), Some(chunk_size))
+                .expect("failed to generate manifest");
+        assert_eq!(manifest.replica_len, 2048);
+        assert_eq!(manifest.checksums.len(), 4);
+
+        let corrupted = scrub(replica.path(), &manifest).expect("failed to scrub");
+        assert!(corrupted.is_empty());
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(replica.path())
+            .expect("failed to open replica");
+        file.seek(SeekFrom::Start(chunk_size * 2 + 5))
+            .expect("failed to seek");
+        file.write_all(&[0xff]).expect("failed to corrupt replica");
+
+        let corrupted = scrub(replica.path(), &manifest).expect("failed to scrub");
+        assert_eq!(corrupted, vec![2]);
+
+        file.set_len(chunk_size)
+            .expect("failed to truncate replica");
+        assert!(scrub(replica.path(), &manifest).is_err());
+    }
+
+    #[test]
+    fn test_default_chunk_size() {
+        let replica = replica();
+
+        let manifest = generate_replica_manifest(REGISTERED_PROOF, replica.path(), None)
+            .expect("failed to generate manifest");
+        assert_eq!(manifest.chunk_size, DEFAULT_CHUNK_SIZE);
+        assert_eq!(manifest.checksums.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        let replica = replica();
+
+        let manifest = generate_replica_manifest(REGISTERED_PROOF, replica.path(), Some(512))
+            .expect("failed to generate manifest");
+
+        // A truncated manifest must not report the remaining chunks as clean.
+        let mut truncated = manifest.clone();
+        truncated.checksums.pop();
+        let err = scrub(replica.path(), &truncated).expect_err("truncated manifest");
+        assert_eq!(
+            err.to_string(),
+            "replica has 4 chunks, manifest has 3 checksums"
+        );
+
+        let mut wrong_proof = manifest;
+        wrong_proof.registered_proof = RegisteredSealProof::StackedDrg8MiBV1_1;
+        assert!(scrub(replica.path(), &wrong_proof).is_err());
+
+        assert!(generate_replica_manifest(
+            RegisteredSealProof::StackedDrg8MiBV1_1,
+            replica.path(),
+            None
+        )
+        .is_err());
+    }
+}
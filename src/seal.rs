@@ -2,8 +2,9 @@ use std::convert::TryInto;
 use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
 
-use anyhow::{bail, ensure, Error, Result};
-use bellperson::bls::Fr;
+use anyhow::{bail, ensure, Context, Error, Result};
+use bellperson::bls::{Bls12, Fr};
+use bellperson::groth16;
 use filecoin_hashers::Hasher;
 
 use filecoin_proofs_v1::constants::{
@@ -281,6 +282,34 @@ pub fn clear_cache(sector_size: u64, cache_path: &Path) -> Result<()> {
     with_shape!(sector_size, clear_cache, cache_path)
 }
 
+/// Loads the Groth parameters and verifying keys for the given proofs into the in-memory
+/// parameter cache, so the first seal does not pay for loading them from disk.
+pub fn warm_up(registered_proofs: &[RegisteredSealProof]) -> Result<()> {
+    for registered_proof in registered_proofs {
+        ensure!(
+            registered_proof.major_version() == 1,
+            "unusupported version"
+        );
+
+        with_shape!(
+            u64::from(registered_proof.sector_size()),
+            warm_up_inner,
+            *registered_proof,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn warm_up_inner<Tree: 'static + MerkleTreeTrait>(
+    registered_proof: RegisteredSealProof,
+) -> Result<()> {
+    filecoin_proofs_v1::caches::get_stacked_params::<Tree>(registered_proof.as_v1_config())?;
+    filecoin_proofs_v1::caches::get_stacked_verifying_key::<Tree>(registered_proof.as_v1_config())?;
+
+    Ok(())
+}
+
 pub fn seal_pre_commit_phase1<R, S, T>(
     registered_proof: RegisteredSealProof,
     cache_path: R,
@@ -794,6 +823,51 @@ pub fn fauxrep2<R: AsRef<Path>, S: AsRef<Path>>(
     }
 }
 
+/// Cheap structural checks on a seal proof, without any pairings.
+///
+/// Checks that the proof has the length expected for `registered_proof` and that every
+/// partition proof consists of valid curve points. A proof passing this check may still be
+/// invalid; use `verify_seal` for the full check.
+pub fn prevalidate_seal_proof(
+    registered_proof: RegisteredSealProof,
+    proof_vec: &[u8],
+) -> Result<()> {
+    ensure!(
+        registered_proof.major_version() == 1,
+        "unusupported version"
+    );
+
+    let expected_len =
+        registered_proof.partitions() as usize * registered_proof.single_partition_proof_len();
+    ensure!(
+        proof_vec.len() == expected_len,
+        "invalid proof length {}, expected {}",
+        proof_vec.len(),
+        expected_len
+    );
+
+    prevalidate_groth_proofs(proof_vec, registered_proof.single_partition_proof_len())
+}
+
+/// Deserializes each `single_proof_len` sized chunk of `proof_bytes` as a Groth proof, which
+/// rejects points that are not on the curve or not in the right subgroup.
+pub(crate) fn prevalidate_groth_proofs(proof_bytes: &[u8], single_proof_len: usize) -> Result<()> {
+    ensure!(!proof_bytes.is_empty(), "empty proof");
+    ensure!(
+        proof_bytes.len() % single_proof_len == 0,
+        "proof length {} is not a multiple of {}",
+        proof_bytes.len(),
+        single_proof_len
+    );
+
+    for (i, chunk) in proof_bytes.chunks(single_proof_len).enumerate() {
+        groth16::Proof::<Bls12>::read(chunk)
+            .with_context(|| format!("invalid partition proof {}", i))?;
+    }
+
+    Ok(())
+}
+
 pub fn verify_seal(
     registered_proof: RegisteredSealProof,
     comm_r_in: Commitment,
@@ -1260,3 +1334,44 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use filecoin_proofs_v1::SINGLE_PARTITION_PROOF_LEN;
+
+    #[test]
+    fn test_prevalidate_seal_proof_length() {
+        let registered_proof = RegisteredSealProof::StackedDrg32GiBV1_1;
+        let partitions = registered_proof.partitions() as usize;
+        assert!(partitions > 1);
+
+        let proof = vec![0u8; (partitions - 1) * SINGLE_PARTITION_PROOF_LEN];
+        let err = prevalidate_seal_proof(registered_proof, &proof).expect_err("short proof");
+        assert!(err.to_string().contains("invalid proof length"));
+
+        assert!(prevalidate_seal_proof(registered_proof, &[]).is_err());
+    }
+
+    #[test]
+    fn test_prevalidate_groth_proofs() {
+        let err = prevalidate_groth_proofs(&[], SINGLE_PARTITION_PROOF_LEN).expect_err("empty");
+        assert_eq!(err.to_string(), "empty proof");
+
+        let proof = vec![0u8; SINGLE_PARTITION_PROOF_LEN + 1];
+        let err = prevalidate_groth_proofs(&proof, SINGLE_PARTITION_PROOF_LEN)
+            .expect_err("proof length is not a multiple");
+        assert!(err.to_string().contains("is not a multiple of"));
+
+        // Not a valid compressed curve point.
+        let proof = vec![0xffu8; SINGLE_PARTITION_PROOF_LEN];
+        let err = prevalidate_groth_proofs(&proof, SINGLE_PARTITION_PROOF_LEN)
+            .expect_err("garbage proof");
+        assert_eq!(err.to_string(), "invalid partition proof 0");
+
+        let err = prevalidate_seal_proof(RegisteredSealProof::StackedDrg2KiBV1_1, &proof)
+            .expect_err("garbage seal proof");
+        assert_eq!(err.to_string(), "invalid partition proof 0");
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+use crate::{
+    post, seal, ChallengeSeed, Commitment, ProverId, PublicReplicaInfo, RegisteredPoStProof,
+    RegisteredSealProof, SectorId, Ticket,
+};
+
+type Digest = [u8; 32];
+
+/// A bounded, least recently used cache of verification results.
+///
+/// Entries are keyed by a digest over the registered proof, the public inputs and the proof
+/// bytes, so a proof that has already been checked is not verified again. Only successful
+/// verifications, valid or not, are cached; errors are always returned to the caller.
+#[derive(Debug)]
+pub struct VerificationCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+/// Recency is tracked with a counter: every access stamps the entry with the next tick, and
+/// `order` maps ticks back to keys, so the least recently used entry is the first one in `order`.
+#[derive(Debug, Default)]
+struct Entries {
+    results: HashMap<Digest, (bool, u64)>,
+    order: BTreeMap<u64, Digest>,
+    tick: u64,
+}
+
+impl Entries {
+    /// Marks `key` as most recently used, forgetting its previous tick if it had one.
+    fn touch(&mut self, key: Digest, valid: bool) {
+        self.tick += 1;
+        if let Some((_, tick)) = self.results.insert(key, (valid, self.tick)) {
+            self.order.remove(&tick);
+        }
+        self.order.insert(self.tick, key);
+    }
+}
+
+impl VerificationCache {
+    pub fn new(capacity: usize) -> Self {
+        VerificationCache {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("verification cache lock poisoned")
+            .results
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("verification cache lock poisoned");
+        entries.results.clear();
+        entries.order.clear();
+    }
+
+    /// Cached version of `seal::verify_seal`.
+    pub fn verify_seal(
+        &self,
+        registered_proof: RegisteredSealProof,
+        comm_r_in: Commitment,
+        comm_d_in: Commitment,
+        prover_id: ProverId,
+        sector_id: SectorId,
+        ticket: Ticket,
+        seed: Ticket,
+        proof_vec: &[u8],
+    ) -> Result<bool> {
+        let mut hasher = new_hasher();
+        hasher.update(b"seal");
+        hasher.update(&(registered_proof as u64).to_le_bytes());
+        hasher.update(&comm_r_in);
+        hasher.update(&comm_d_in);
+        hasher.update(&prover_id);
+        hasher.update(&u64::from(sector_id).to_le_bytes());
+        hasher.update(&ticket);
+        hasher.update(&seed);
+        hash_bytes(&mut hasher, proof_vec);
+
+        self.get_or_verify(finalize(hasher), || {
+            seal::verify_seal(
+                registered_proof,
+                comm_r_in,
+                comm_d_in,
+                prover_id,
+                sector_id,
+                ticket,
+                seed,
+                proof_vec,
+            )
+        })
+    }
+
+    /// Cached version of `post::verify_winning_post`.
+    pub fn verify_winning_post(
+        &self,
+        randomness: &ChallengeSeed,
+        proof: &[u8],
+        replicas: &BTreeMap<SectorId, PublicReplicaInfo>,
+        prover_id: ProverId,
+    ) -> Result<bool> {
+        let mut hasher = new_hasher();
+        hasher.update(b"winning_post");
+        hash_post_inputs(&mut hasher, randomness, replicas, prover_id);
+        hash_bytes(&mut hasher, proof);
+
+        self.get_or_verify(finalize(hasher), || {
+            post::verify_winning_post(randomness, proof, replicas, prover_id)
+        })
+    }
+
+    /// Cached version of `post::verify_window_post`.
+    pub fn verify_window_post(
+        &self,
+        randomness: &ChallengeSeed,
+        proofs: &[(RegisteredPoStProof, &[u8])],
+        replicas: &BTreeMap<SectorId, PublicReplicaInfo>,
+        prover_id: ProverId,
+    ) -> Result<bool> {
+        let mut hasher = new_hasher();
+        hasher.update(b"window_post");
+        hash_post_inputs(&mut hasher, randomness, replicas, prover_id);
+        hasher.update(&(proofs.len() as u64).to_le_bytes());
+        for (registered_proof, proof) in proofs {
+            hasher.update(&(*registered_proof as u64).to_le_bytes());
+            hash_bytes(&mut hasher, proof);
+        }
+
+        self.get_or_verify(finalize(hasher), || {
+            post::verify_window_post(randomness, proofs, replicas, prover_id)
+        })
+    }
+
+    fn get_or_verify<F>(&self, key: Digest, verify: F) -> Result<bool>
+    where
+        F: FnOnce() -> Result<bool>,
+    {
+        if let Some(valid) = self.get(&key) {
+            return Ok(valid);
+        }
+
+        // Verify without holding the lock, concurrent verifications of the same proof are
+        // harmless.
+        let valid = verify()?;
+        self.insert(key, valid);
+
+        Ok(valid)
+    }
+
+    fn get(&self, key: &Digest) -> Option<bool> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("verification cache lock poisoned");
+        let (valid, _) = *entries.results.get(key)?;
+        entries.touch(*key, valid);
+
+        Some(valid)
+    }
+
+    fn insert(&self, key: Digest, valid: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("verification cache lock poisoned");
+        entries.touch(key, valid);
+
+        while entries.order.len() > self.capacity {
+            let oldest = *entries.order.keys().next().expect("order is not empty");
+            if let Some(evicted) = entries.order.remove(&oldest) {
+                entries.results.remove(&evicted);
+            }
+        }
+    }
+}
+
+fn new_hasher() -> blake2b_simd::State {
+    blake2b_simd::Params::new().hash_length(32).to_state()
+}
+
+fn finalize(hasher: blake2b_simd::State) -> Digest {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(hasher.finalize().as_bytes());
+    digest
+}
+
+/// Length prefixed, so that variable sized inputs cannot be shifted into each other.
+fn hash_bytes(hasher: &mut blake2b_simd::State, bytes: &[u8]) {
+    hasher.update(&(bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+fn hash_post_inputs(
+    hasher: &mut blake2b_simd::State,
+    randomness: &ChallengeSeed,
+    replicas: &BTreeMap<SectorId, PublicReplicaInfo>,
+    prover_id: ProverId,
+) {
+    hasher.update(randomness);
+    hasher.update(&prover_id);
+    hasher.update(&(replicas.len() as u64).to_le_bytes());
+    for (sector_id, replica) in replicas {
+        hasher.update(&u64::from(*sector_id).to_le_bytes());
+        hasher.update(&(replica.registered_proof as u64).to_le_bytes());
+        hasher.update(&replica.comm_r);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = VerificationCache::new(2);
+
+        cache.insert([1; 32], true);
+        cache.insert([2; 32], false);
+        assert_eq!(cache.get(&[1; 32]), Some(true));
+
+        // [2; 32] is now the least recently used entry.
+        cache.insert([3; 32], true);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&[2; 32]), None);
+        assert_eq!(cache.get(&[1; 32]), Some(true));
+        assert_eq!(cache.get(&[3; 32]), Some(true));
+    }
+
+    #[test]
+    fn test_lru_reinsert_order() {
+        let cache = VerificationCache::new(3);
+
+        cache.insert([1; 32], true);
+        cache.insert([2; 32], true);
+        cache.insert([3; 32], true);
+
+        // Reinserting and reading move entries to the back, oldest first is now 3, 1, 2.
+        cache.insert([1; 32], false);
+        cache.insert([2; 32], true);
+        cache.insert([1; 32], true);
+        assert_eq!(cache.get(&[2; 32]), Some(true));
+        assert_eq!(cache.len(), 3);
+
+        cache.insert([4; 32], true);
+        assert_eq!(cache.get(&[3; 32]), None);
+
+        cache.insert([5; 32], true);
+        assert_eq!(cache.get(&[1; 32]), None);
+
+        cache.insert([6; 32], true);
+        assert_eq!(cache.get(&[2; 32]), None);
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(&[4; 32]), Some(true));
+        assert_eq!(cache.get(&[5; 32]), Some(true));
+        assert_eq!(cache.get(&[6; 32]), Some(true));
+    }
+
+    #[test]
+    fn test_get_or_verify() {
+        let cache = VerificationCache::new(4);
+
+        let valid = cache
+            .get_or_verify([7; 32], || Ok(true))
+            .expect("verification failed");
+        assert!(valid);
+
+        let valid = cache
+            .get_or_verify([7; 32], || panic!("cached result not used"))
+            .expect("verification failed");
+        assert!(valid);
+
+        assert!(cache
+            .get_or_verify([8; 32], || anyhow::bail!("invalid proof"))
+            .is_err());
+        assert_eq!(cache.len(), 1);
+    }
+}